@@ -3,6 +3,7 @@
 //! Commands callable from the frontend via invoke().
 
 use serde::Serialize;
+use sysinfo::{Pid, ProcessRefreshKind, RefreshKind, System};
 
 /// Simple greeting command for testing
 #[tauri::command]
@@ -27,8 +28,69 @@ pub fn get_system_info() -> SystemInfo {
     }
 }
 
-/// Retourne le port du backend (dynamique en release, 8000 en dev)
+/// Retourne le port du backend (alloué dynamiquement en release et en dev
+/// avec `THERESE_DEV_SIDECAR`, fixe en dev sinon)
 #[tauri::command]
 pub fn get_backend_port(state: tauri::State<'_, crate::BackendPort>) -> u16 {
     *state.0.lock().unwrap()
 }
+
+/// Utilisation ressources du sidecar backend, pour le panneau de debug
+/// (utile quand des modèles sentence-transformers sont chargés en mémoire).
+#[derive(Serialize)]
+pub struct SidecarStats {
+    pub pid: u32,
+    pub cpu_percent: f32,
+    pub memory_bytes: u64,
+    pub uptime_secs: u64,
+    pub status: String,
+}
+
+/// Retourne l'utilisation CPU/RAM du sidecar backend.
+///
+/// PyInstaller `--onefile` lance un bootloader qui spawn un process Python
+/// enfant ; on additionne donc la RAM/CPU des enfants directs du bootloader
+/// pour que le chiffre reflète le vrai footprint (modèles chargés côté
+/// Python), pas juste le bootloader lui-même.
+#[tauri::command]
+pub fn get_sidecar_stats(
+    state: tauri::State<'_, crate::SidecarState>,
+) -> Result<SidecarStats, String> {
+    let pid = *state.pid.lock().unwrap();
+    let Some(pid) = pid else {
+        return Err("Sidecar non démarré".to_string());
+    };
+
+    let mut sys = System::new_with_specifics(
+        RefreshKind::new().with_processes(ProcessRefreshKind::everything()),
+    );
+    // `cpu_usage()` est une moyenne entre deux refresh : un seul appel
+    // renvoie toujours 0.0, il faut laisser passer l'intervalle minimum.
+    sys.refresh_processes();
+    std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    sys.refresh_processes();
+
+    let sys_pid = Pid::from_u32(pid);
+    let process = sys
+        .process(sys_pid)
+        .ok_or_else(|| "Process sidecar introuvable".to_string())?;
+
+    let mut cpu_percent = process.cpu_usage();
+    let mut memory_bytes = process.memory();
+    let uptime_secs = process.run_time();
+
+    for (_, child_process) in sys.processes() {
+        if child_process.parent() == Some(sys_pid) {
+            cpu_percent += child_process.cpu_usage();
+            memory_bytes += child_process.memory();
+        }
+    }
+
+    Ok(SidecarStats {
+        pid,
+        cpu_percent,
+        memory_bytes,
+        uptime_secs,
+        status: "running".to_string(),
+    })
+}
@@ -0,0 +1,92 @@
+//! Rechargement à chaud du sidecar en développement.
+//!
+//! Optionnel (activé via `THERESE_DEV_SIDECAR`/`THERESE_DEV_WATCH_PATH`,
+//! cf. `lib.rs`) : surveille un chemin configurable (sources Python ou
+//! binaire `backend` rebuildé) et redémarre le sidecar à chaque
+//! changement, avec un debounce pour coalescer les sauvegardes
+//! rapprochées.
+
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use notify::{RecursiveMode, Watcher};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{log_sidecar, BackendPort, SidecarState};
+
+use super::shutdown;
+use super::spawn::spawn_sidecar;
+
+/// Fenêtre de debounce : les événements rapprochés (plusieurs fichiers
+/// sauvegardés d'un coup) sont coalescés en un seul redémarrage.
+const DEBOUNCE_WINDOW: Duration = Duration::from_millis(500);
+
+/// Démarre la surveillance de `watch_path` dans un thread dédié et
+/// redémarre le sidecar sur changement. Les événements rapprochés sont
+/// coalescés en un seul redémarrage par le debounce ci-dessous.
+pub fn start(app: AppHandle, watch_path: PathBuf) {
+    std::thread::spawn(move || {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut watcher = match notify::recommended_watcher(tx) {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                log_sidecar(&format!("Hot reload : watcher indisponible ({})", e));
+                return;
+            }
+        };
+
+        if let Err(e) = watcher.watch(&watch_path, RecursiveMode::Recursive) {
+            log_sidecar(&format!(
+                "Hot reload : impossible de surveiller {} ({})",
+                watch_path.display(),
+                e
+            ));
+            return;
+        }
+
+        log_sidecar(&format!(
+            "Hot reload : surveillance de {}",
+            watch_path.display()
+        ));
+
+        loop {
+            // Bloque jusqu'au premier événement, puis absorbe tout ce qui
+            // arrive pendant la fenêtre de debounce avant de redémarrer.
+            if rx.recv().is_err() {
+                break;
+            }
+            while rx.recv_timeout(DEBOUNCE_WINDOW).is_ok() {}
+
+            log_sidecar("Hot reload : changement détecté, redémarrage du sidecar...");
+            restart_sidecar(&app);
+            let _ = app.emit("sidecar-reloaded", ());
+        }
+    });
+}
+
+/// Arrête le sidecar actuel puis le relance avec les mêmes arguments/env
+/// (même port, cf. `BackendPort`).
+fn restart_sidecar(app: &AppHandle) {
+    let port = *app.state::<BackendPort>().0.lock().unwrap();
+    let state = app.state::<SidecarState>();
+
+    let old_pid = state.pid.lock().unwrap().take();
+    #[cfg(windows)]
+    let old_job = state.job.lock().unwrap().take();
+    if let Some(old_pid) = old_pid {
+        // Ce kill est volontaire : on le signale avant de tuer le process
+        // pour que le thread de wait() ne le remonte pas comme un crash
+        // (ce qui redéclencherait une alerte superviseur pour rien).
+        state.expected_exit.store(true, Ordering::SeqCst);
+        shutdown::kill_sidecar(
+            old_pid,
+            #[cfg(windows)]
+            old_job.as_ref(),
+        );
+    }
+
+    if let Err(e) = spawn_sidecar(app, port) {
+        log_sidecar(&format!("Hot reload : échec du redémarrage ({})", e));
+    }
+}
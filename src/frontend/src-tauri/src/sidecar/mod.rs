@@ -0,0 +1,9 @@
+//! Gestion du sidecar backend THÉRÈSE : détection/arrêt des process,
+//! allocation de port, supervision et (en dev) rechargement à chaud.
+
+pub mod dev_reload;
+pub mod port;
+pub mod process;
+pub mod shutdown;
+pub mod spawn;
+pub mod supervisor;
@@ -0,0 +1,40 @@
+//! Allocation du port TCP utilisé par le sidecar backend.
+
+use std::net::TcpListener;
+
+use crate::log_sidecar;
+
+/// Ports testés si l'allocation d'un port éphémère (`:0`) échoue.
+const PREFERRED_PORTS: [u16; 5] = [17293, 17294, 17295, 17296, 17297];
+
+/// Demande à l'OS un port éphémère libre sur `127.0.0.1`.
+fn bind_ephemeral_port() -> Option<u16> {
+    let listener = TcpListener::bind("127.0.0.1:0").ok()?;
+    let port = listener.local_addr().ok()?.port();
+    // Le listener est abandonné immédiatement : on ne veut que réserver le
+    // port le temps de le lire, le sidecar fera son propre bind dessus.
+    drop(listener);
+    Some(port)
+}
+
+/// Alloue un port TCP libre pour le sidecar backend.
+///
+/// Laisse l'OS choisir un port éphémère via un bind sur `127.0.0.1:0`
+/// (évite tout conflit, y compris entre plusieurs instances de l'app).
+/// Si ce bind échoue pour une raison quelconque, retombe sur un scan
+/// d'une petite liste de ports préférés.
+pub fn allocate_port() -> u16 {
+    if let Some(port) = bind_ephemeral_port() {
+        return port;
+    }
+
+    log_sidecar("Allocation d'un port éphémère impossible, scan des ports préférés...");
+    for &port in &PREFERRED_PORTS {
+        if TcpListener::bind(("127.0.0.1", port)).is_ok() {
+            return port;
+        }
+    }
+
+    log_sidecar("Aucun port préféré libre, utilisation du port par défaut");
+    PREFERRED_PORTS[0]
+}
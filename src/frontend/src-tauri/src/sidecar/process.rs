@@ -0,0 +1,116 @@
+//! Détection et arrêt des process backend zombies.
+//!
+//! Remplace l'ancienne implémentation `pgrep`/`kill`/`wmic`/`tasklist` par
+//! un scan natif via `sysinfo`, sans dépendre d'outils externes qui varient
+//! (voire disparaissent, cf. `wmic` retiré dans Windows 11 25H2) selon la
+//! plateforme et la version d'OS.
+
+use sysinfo::{Pid, ProcessRefreshKind, RefreshKind, Signal, System};
+
+use crate::log_sidecar;
+
+/// Un process correspond à la signature du backend THÉRÈSE si son nom
+/// d'exécutable est `backend`/`backend.exe` et que sa ligne de commande
+/// contient `--host 127.0.0.1`.
+fn is_backend_process(name: &str, cmd: &[String]) -> bool {
+    let name_matches = name == "backend" || name == "backend.exe";
+    let cmd_matches = cmd.iter().any(|arg| arg == "--host")
+        && cmd.iter().any(|arg| arg == "127.0.0.1");
+    name_matches && cmd_matches
+}
+
+/// Sur Unix, PyInstaller `--onefile` lance un bootloader qui spawn un
+/// process Python enfant : on répercute le signal sur les enfants directs
+/// de `parent_pid` pour ne pas laisser le second process orphelin.
+#[cfg(unix)]
+fn kill_children(sys: &System, parent_pid: Pid, signal: Signal) {
+    for (pid, process) in sys.processes() {
+        if process.parent() == Some(parent_pid) {
+            process.kill_with(signal);
+        }
+    }
+}
+
+/// Tue les anciens process backend THÉRÈSE zombies restés actifs.
+/// Nécessaire lors des mises à jour (ex: v0.1.4 → v0.1.5+).
+pub fn kill_zombie_backends() {
+    log_sidecar("Recherche de process backend zombies...");
+
+    let current_pid = Pid::from_u32(std::process::id());
+    let mut sys = System::new_with_specifics(
+        RefreshKind::new().with_processes(ProcessRefreshKind::everything()),
+    );
+    sys.refresh_processes();
+
+    let zombies: Vec<Pid> = sys
+        .processes()
+        .iter()
+        .filter(|(pid, process)| {
+            **pid != current_pid && is_backend_process(process.name(), process.cmd())
+        })
+        .map(|(pid, _)| *pid)
+        .collect();
+
+    if zombies.is_empty() {
+        log_sidecar("Aucun zombie détecté");
+    } else {
+        for pid in &zombies {
+            log_sidecar(&format!("Zombie détecté (PID: {}), SIGTERM...", pid));
+            if let Some(process) = sys.process(*pid) {
+                process.kill_with(Signal::Term);
+            }
+            #[cfg(unix)]
+            kill_children(&sys, *pid, Signal::Term);
+        }
+
+        std::thread::sleep(std::time::Duration::from_secs(2));
+
+        // Force kill les survivants
+        sys.refresh_processes();
+        for pid in &zombies {
+            if let Some(process) = sys.process(*pid) {
+                log_sidecar(&format!("Zombie résistant (PID: {}), SIGKILL...", pid));
+                process.kill();
+                #[cfg(unix)]
+                kill_children(&sys, *pid, Signal::Kill);
+            }
+        }
+    }
+
+    cleanup_runtime_artifacts();
+
+    log_sidecar("Nettoyage des zombies terminé");
+}
+
+/// Nettoie le fichier `.lock` Qdrant et les dossiers PyInstaller résiduels.
+fn cleanup_runtime_artifacts() {
+    let Some(home) = dirs::home_dir() else {
+        return;
+    };
+
+    let lock_file = home.join(".therese").join("qdrant").join(".lock");
+    if lock_file.exists() {
+        log_sidecar("Nettoyage du fichier .lock Qdrant...");
+        let _ = std::fs::remove_file(&lock_file);
+    }
+
+    // Nettoyer les anciens dossiers _MEI* residuels de PyInstaller
+    // Un crash pendant l'extraction laisse un dossier incomplet qui bloque
+    // les lancements suivants
+    let runtime_dir = home.join(".therese").join("runtime");
+    if runtime_dir.exists() {
+        if let Ok(entries) = std::fs::read_dir(&runtime_dir) {
+            for entry in entries.flatten() {
+                let name = entry.file_name();
+                let name_str = name.to_string_lossy();
+                if name_str.starts_with("_MEI") && entry.path().is_dir() {
+                    log_sidecar(&format!(
+                        "Nettoyage ancien dossier PyInstaller : {}",
+                        name_str
+                    ));
+                    let _ = std::fs::remove_dir_all(entry.path());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//! Arrêt propre du sidecar basé sur les groupes de process.
+//!
+//! Sur Unix le sidecar est lancé dans son propre process group (voir
+//! `configure_process_group`) : on peut alors envoyer un signal au group
+//! entier (`kill(-pgid, ...)`) et atteindre à la fois le bootloader
+//! PyInstaller et son enfant Python en un seul appel. Sur Windows
+//! l'équivalent est un Job Object : terminer le job tue tout l'arbre de
+//! process de façon atomique.
+//!
+//! Dans les deux cas on attend la mort du process par polling plutôt que
+//! par un `sleep` fixe, pour rendre la main dès qu'un arrêt propre a réussi
+//! et forcer au bout d'un délai sinon.
+
+use std::time::{Duration, Instant};
+
+/// Intervalle de polling pendant l'attente de la mort du process.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Délai laissé au sidecar pour répondre à `/api/shutdown` (uvicorn +
+/// lifespan cleanup, peut charger/décharger des modèles).
+const HTTP_SHUTDOWN_DEADLINE: Duration = Duration::from_secs(10);
+/// Délai laissé au process group après un SIGTERM avant le SIGKILL.
+const SIGTERM_DEADLINE: Duration = Duration::from_secs(5);
+
+use crate::log_sidecar;
+
+/// Configure la `Command` pour que le sidecar démarre comme leader de son
+/// propre process group (`setpgid(0, 0)` dans l'enfant juste après le
+/// fork). Nécessaire pour pouvoir ensuite signaler tout l'arbre de process
+/// d'un coup.
+#[cfg(unix)]
+pub fn configure_process_group(command: &mut std::process::Command) {
+    use std::os::unix::process::CommandExt;
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setpgid(0, 0) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(unix)]
+fn is_alive(pid: u32) -> bool {
+    // kill(pid, 0) n'envoie aucun signal, il vérifie juste l'existence du
+    // process (et nos permissions dessus).
+    unsafe { libc::kill(pid as libc::pid_t, 0) == 0 }
+}
+
+#[cfg(unix)]
+fn signal_group(pid: u32, signal: libc::c_int) {
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), signal);
+    }
+}
+
+#[cfg(windows)]
+fn is_alive(pid: u32) -> bool {
+    use windows_sys::Win32::Foundation::{CloseHandle, STILL_ACTIVE};
+    use windows_sys::Win32::System::Threading::{
+        GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return false;
+        }
+        let mut exit_code: u32 = 0;
+        let ok = GetExitCodeProcess(handle, &mut exit_code);
+        CloseHandle(handle);
+        ok != 0 && exit_code == STILL_ACTIVE as u32
+    }
+}
+
+/// Poll jusqu'à ce que `pid` ne soit plus vivant ou que `deadline` soit
+/// atteinte. Retourne `true` si le process est bien mort.
+fn wait_until_exited(pid: u32, deadline: Instant) -> bool {
+    while Instant::now() < deadline {
+        if !is_alive(pid) {
+            return true;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    !is_alive(pid)
+}
+
+/// Envoie `POST /api/shutdown` au backend (best effort, ignore les erreurs :
+/// si le backend ne répond pas on passera de toute façon par l'escalade
+/// SIGTERM/SIGKILL ci-dessous). Retourne `false` si le backend est
+/// injoignable, auquel cas l'attente du shutdown HTTP est sautée.
+fn request_http_shutdown(port: u16) -> bool {
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    let addr: std::net::SocketAddr = match format!("127.0.0.1:{}", port).parse() {
+        Ok(addr) => addr,
+        Err(_) => return false,
+    };
+    let Ok(mut stream) = TcpStream::connect_timeout(&addr, Duration::from_secs(2)) else {
+        log_sidecar("Backend injoignable (timeout 2s), passage au process group");
+        return false;
+    };
+    let request = format!(
+        "POST /api/shutdown HTTP/1.1\r\nHost: 127.0.0.1:{}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        port
+    );
+    let _ = stream.write_all(request.as_bytes());
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(2)));
+    let mut buf = [0u8; 256];
+    let _ = stream.read(&mut buf);
+    log_sidecar("Shutdown HTTP envoyé, attente du shutdown graceful...");
+    true
+}
+
+/// Windows : Job Object auquel le sidecar est assigné à son lancement.
+/// Le terminer (`TerminateJobObject`) tue atomiquement tout l'arbre de
+/// process (bootloader PyInstaller + enfant Python), sans avoir à
+/// énumérer les enfants nous-mêmes.
+#[cfg(windows)]
+pub struct JobObject(windows_sys::Win32::Foundation::HANDLE);
+
+#[cfg(windows)]
+impl JobObject {
+    /// Crée un Job Object configuré pour tuer tous ses process quand le
+    /// handle est fermé (filet de sécurité si on oublie de le terminer
+    /// explicitement).
+    pub fn create() -> Option<Self> {
+        use windows_sys::Win32::System::JobObjects::{
+            CreateJobObjectW, JobObjectExtendedLimitInformation, SetInformationJobObject,
+            JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+        };
+
+        unsafe {
+            let handle = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+            if handle.is_null() {
+                return None;
+            }
+
+            let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+            info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            let ok = SetInformationJobObject(
+                handle,
+                JobObjectExtendedLimitInformation,
+                &info as *const _ as *const _,
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            );
+            if ok == 0 {
+                windows_sys::Win32::Foundation::CloseHandle(handle);
+                return None;
+            }
+
+            Some(Self(handle))
+        }
+    }
+
+    /// Assigne `child` à ce job. Doit être appelé juste après le spawn,
+    /// avant que le process n'ait eu l'occasion de créer ses propres
+    /// enfants (sans quoi ceux-ci échapperaient au job).
+    pub fn assign(&self, child: &std::process::Child) -> bool {
+        use std::os::windows::io::AsRawHandle;
+        use windows_sys::Win32::System::JobObjects::AssignProcessToJobObject;
+        unsafe { AssignProcessToJobObject(self.0, child.as_raw_handle() as _) != 0 }
+    }
+
+    /// Tue immédiatement tout l'arbre de process du job.
+    pub fn terminate(&self) {
+        unsafe {
+            windows_sys::Win32::System::JobObjects::TerminateJobObject(self.0, 1);
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for JobObject {
+    fn drop(&mut self) {
+        unsafe {
+            windows_sys::Win32::Foundation::CloseHandle(self.0);
+        }
+    }
+}
+
+// SAFETY: le HANDLE n'est manipulé qu'au travers des appels Win32 ci-dessus,
+// qui sont thread-safe côté OS.
+#[cfg(windows)]
+unsafe impl Send for JobObject {}
+#[cfg(windows)]
+unsafe impl Sync for JobObject {}
+
+/// Arrêt complet et propre du sidecar : `/api/shutdown` HTTP, puis
+/// escalade SIGTERM / SIGKILL du process group (Unix) ou terminaison du
+/// Job Object (Windows) si le process ne s'arrête pas à temps. Poll
+/// jusqu'à la mort du process plutôt qu'un `sleep` fixe.
+pub fn shutdown_sidecar(
+    pid: u32,
+    port: u16,
+    #[cfg(windows)] job: Option<&JobObject>,
+) {
+    log_sidecar(&format!("Arrêt du sidecar (PID: {}, port: {})", pid, port));
+
+    if request_http_shutdown(port)
+        && wait_until_exited(pid, Instant::now() + HTTP_SHUTDOWN_DEADLINE)
+    {
+        log_sidecar("Sidecar terminé proprement (shutdown HTTP)");
+        return;
+    }
+
+    #[cfg(unix)]
+    {
+        log_sidecar("Sidecar encore actif, SIGTERM au process group...");
+        signal_group(pid, libc::SIGTERM);
+        if wait_until_exited(pid, Instant::now() + SIGTERM_DEADLINE) {
+            log_sidecar("Process group terminé après SIGTERM");
+            return;
+        }
+        log_sidecar("Process group résistant, SIGKILL...");
+        signal_group(pid, libc::SIGKILL);
+    }
+
+    #[cfg(windows)]
+    {
+        log_sidecar("Sidecar encore actif, terminaison du Job Object...");
+        if let Some(job) = job {
+            job.terminate();
+        }
+    }
+
+    log_sidecar("Nettoyage terminé");
+}
+
+/// Arrêt rapide sans tentative HTTP, utilisé par le superviseur quand le
+/// sidecar est jugé mort/bloqué (health-checks en échec) : pas de
+/// politesse à attendre d'un process qui ne répond déjà plus.
+pub fn kill_sidecar(pid: u32, #[cfg(windows)] job: Option<&JobObject>) {
+    #[cfg(unix)]
+    {
+        signal_group(pid, libc::SIGTERM);
+        if wait_until_exited(pid, Instant::now() + Duration::from_secs(2)) {
+            return;
+        }
+        signal_group(pid, libc::SIGKILL);
+    }
+
+    #[cfg(windows)]
+    {
+        if let Some(job) = job {
+            job.terminate();
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Construction et lancement du process sidecar backend.
+//!
+//! Factorisé à part du `setup()` de `lib.rs` pour être réutilisable aussi
+//! bien au premier démarrage que lors d'un redémarrage piloté par
+//! `sidecar::supervisor`. Le sidecar est lancé via `std::process::Command`
+//! plutôt que via le plugin shell, pour pouvoir le placer dans son propre
+//! process group (Unix) / Job Object (Windows) — cf. `sidecar::shutdown`.
+
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::Ordering;
+
+use tauri::{AppHandle, Manager};
+
+use crate::{log_sidecar, SidecarState};
+
+use super::shutdown;
+
+/// Localise le binaire sidecar, placé à côté de l'exécutable principal
+/// par le bundler Tauri (entrée `externalBin`).
+fn resolve_sidecar_path() -> Result<PathBuf, String> {
+    let current_exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let dir = current_exe
+        .parent()
+        .ok_or_else(|| "Impossible de déterminer le dossier de l'exécutable".to_string())?;
+
+    #[cfg(windows)]
+    let name = "backend.exe";
+    #[cfg(not(windows))]
+    let name = "backend";
+
+    let path = dir.join(name);
+    if !path.exists() {
+        return Err(format!("Binaire sidecar introuvable : {}", path.display()));
+    }
+    Ok(path)
+}
+
+/// Lance le sidecar backend sur `port` et stocke son PID (et, sur Windows,
+/// son Job Object) dans `SidecarState`. Un `Terminated` inattendu est
+/// répercuté vers `sidecar::supervisor` pour déclencher un redémarrage.
+pub fn spawn_sidecar(app: &AppHandle, port: u16) -> Result<(), String> {
+    let binary = resolve_sidecar_path()?;
+    let port_str = port.to_string();
+
+    let home_dir = dirs::home_dir().unwrap_or_default();
+    let models_path = home_dir
+        .join(".therese/models")
+        .to_string_lossy()
+        .to_string();
+
+    // Rediriger le dossier TEMP du sidecar vers ~/.therese/runtime/
+    // Evite le scan antivirus sur %TEMP% qui bloque l'extraction PyInstaller
+    let runtime_path = home_dir
+        .join(".therese/runtime")
+        .to_string_lossy()
+        .to_string();
+    let _ = std::fs::create_dir_all(&runtime_path);
+
+    log_sidecar(&format!("Démarrage sidecar sur port {}", port));
+
+    let mut command = Command::new(&binary);
+    command
+        .args(["--host", "127.0.0.1", "--port", &port_str])
+        .env("THERESE_PORT", &port_str)
+        .env("THERESE_ENV", "production")
+        .env("SENTENCE_TRANSFORMERS_HOME", &models_path)
+        // PyInstaller --onefile extrait dans TEMP/_MEIxxxx
+        // %TEMP% est scanne par Windows Defender, causant des crashs silencieux
+        // TMPDIR est prioritaire sur macOS/Linux
+        .env("TMPDIR", &runtime_path)
+        .env("TEMP", &runtime_path)
+        .env("TMP", &runtime_path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    #[cfg(unix)]
+    shutdown::configure_process_group(&mut command);
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Erreur lancement sidecar : {}", e))?;
+
+    let pid = child.id();
+    let msg = format!("Sidecar démarré (PID: {}, port: {})", pid, port);
+    println!("[THÉRÈSE] {}", msg);
+    log_sidecar(&msg);
+
+    #[cfg(windows)]
+    let job = shutdown::JobObject::create().filter(|job| job.assign(&child));
+    #[cfg(windows)]
+    if job.is_none() {
+        log_sidecar("Impossible de créer/assigner le Job Object du sidecar");
+    }
+
+    let state = app.state::<SidecarState>();
+    *state.pid.lock().unwrap() = Some(pid);
+    #[cfg(windows)]
+    {
+        *state.job.lock().unwrap() = job;
+    }
+
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+
+    if let Some(stdout) = stdout {
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                println!("[backend] {}", line);
+                log_sidecar(&format!("[stdout] {}", line));
+            }
+        });
+    }
+    if let Some(stderr) = stderr {
+        std::thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                eprintln!("[backend] {}", line);
+                log_sidecar(&format!("[stderr] {}", line));
+            }
+        });
+    }
+
+    let app_handle = app.clone();
+    std::thread::spawn(move || {
+        let status = child.wait();
+        let msg = format!("Sidecar terminé (status: {:?})", status);
+        println!("[THÉRÈSE] {}", msg);
+        log_sidecar(&msg);
+
+        // Un kill volontaire (superviseur/hot reload) positionne ce flag
+        // juste avant de tuer le process : on ne remonte alors pas de
+        // Terminated, sans quoi le superviseur redémarrerait en boucle le
+        // sidecar fraîchement relancé (cf. notify_terminated).
+        let state = app_handle.state::<SidecarState>();
+        if state.expected_exit.swap(false, Ordering::SeqCst) {
+            log_sidecar("Arrêt du sidecar attendu, pas d'alerte superviseur");
+        } else {
+            super::supervisor::notify_terminated(&app_handle);
+        }
+    });
+
+    Ok(())
+}
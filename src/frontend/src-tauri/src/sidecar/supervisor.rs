@@ -0,0 +1,172 @@
+//! Supervision du sidecar : health-check périodique et redémarrage
+//! automatique (avec backoff exponentiel) en cas de crash ou de blocage.
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{log_sidecar, BackendPort, SidecarState};
+
+use super::spawn::spawn_sidecar;
+
+/// Intervalle entre deux health-checks une fois le sidecar lancé.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// Nombre d'échecs consécutifs de health-check avant de considérer le
+/// sidecar comme mort et de déclencher un redémarrage.
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+/// Nombre de redémarrages consécutifs avant d'abandonner.
+const MAX_RETRIES: u32 = 5;
+/// Backoff de base, doublé à chaque tentative jusqu'à `MAX_BACKOFF`.
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// Durée de fonctionnement stable avant de remettre le compteur de
+/// redémarrages à zéro.
+const HEALTHY_RESET_WINDOW: Duration = Duration::from_secs(60);
+
+/// État partagé du superviseur, géré comme state Tauri.
+pub struct SupervisorState {
+    /// Positionné par le thread de `wait()` du sidecar quand celui-ci se
+    /// termine de façon inattendue : le redémarrage est alors déclenché dès
+    /// le tour suivant de la boucle, sans attendre
+    /// `MAX_CONSECUTIVE_FAILURES` échecs de health-check.
+    terminated: AtomicBool,
+    restart_count: AtomicU32,
+}
+
+impl SupervisorState {
+    pub fn new() -> Self {
+        Self {
+            terminated: AtomicBool::new(false),
+            restart_count: AtomicU32::new(0),
+        }
+    }
+}
+
+impl Default for SupervisorState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Appelé par `sidecar::spawn` quand le process sidecar se termine.
+pub fn notify_terminated(app: &AppHandle) {
+    let state = app.state::<SupervisorState>();
+    state.terminated.store(true, Ordering::SeqCst);
+}
+
+fn check_health(port: u16) -> bool {
+    let addr: SocketAddr = match format!("127.0.0.1:{}", port).parse() {
+        Ok(addr) => addr,
+        Err(_) => return false,
+    };
+    let mut stream = match TcpStream::connect_timeout(&addr, Duration::from_secs(2)) {
+        Ok(stream) => stream,
+        Err(_) => return false,
+    };
+    let request = format!(
+        "GET /api/health HTTP/1.1\r\nHost: 127.0.0.1:{}\r\nConnection: close\r\n\r\n",
+        port
+    );
+    if stream.write_all(request.as_bytes()).is_err() {
+        return false;
+    }
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(2)));
+    let mut buf = Vec::new();
+    if stream.read_to_end(&mut buf).is_err() {
+        return false;
+    }
+    String::from_utf8_lossy(&buf).starts_with("HTTP/1.1 200")
+}
+
+/// Démarre la boucle de supervision dans un thread dédié. Health-check
+/// HTTP périodique vers `/api/health`, redémarrage (backoff exponentiel)
+/// sur `Terminated` ou sur `MAX_CONSECUTIVE_FAILURES` échecs consécutifs.
+pub fn start(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut consecutive_failures = 0u32;
+        let mut healthy_since = Instant::now();
+        let mut awaiting_recovery = false;
+
+        loop {
+            std::thread::sleep(HEALTH_CHECK_INTERVAL);
+
+            let supervisor = app.state::<SupervisorState>();
+            let port = *app.state::<BackendPort>().0.lock().unwrap();
+            let terminated = supervisor.terminated.swap(false, Ordering::SeqCst);
+            let healthy = !terminated && check_health(port);
+
+            if healthy {
+                consecutive_failures = 0;
+                if awaiting_recovery {
+                    awaiting_recovery = false;
+                    log_sidecar("Sidecar de nouveau opérationnel après redémarrage");
+                    let _ = app.emit("sidecar-recovered", ());
+                }
+                if healthy_since.elapsed() >= HEALTHY_RESET_WINDOW {
+                    supervisor.restart_count.store(0, Ordering::SeqCst);
+                }
+                continue;
+            }
+
+            healthy_since = Instant::now();
+            if !terminated {
+                consecutive_failures += 1;
+                log_sidecar(&format!(
+                    "Health-check échoué ({}/{})",
+                    consecutive_failures, MAX_CONSECUTIVE_FAILURES
+                ));
+                if consecutive_failures < MAX_CONSECUTIVE_FAILURES {
+                    continue;
+                }
+            }
+            consecutive_failures = 0;
+
+            let attempt = supervisor.restart_count.fetch_add(1, Ordering::SeqCst) + 1;
+            if attempt > MAX_RETRIES {
+                log_sidecar(&format!(
+                    "Sidecar : abandon définitif après {} redémarrages consécutifs",
+                    MAX_RETRIES
+                ));
+                let _ = app.emit("sidecar-failed", ());
+                break;
+            }
+
+            let backoff = std::cmp::min(BASE_BACKOFF * 2u32.pow(attempt - 1), MAX_BACKOFF);
+            log_sidecar(&format!(
+                "Sidecar indisponible, redémarrage {}/{} dans {:?}...",
+                attempt, MAX_RETRIES, backoff
+            ));
+            let _ = app.emit("sidecar-restarting", attempt);
+            std::thread::sleep(backoff);
+
+            let state = app.state::<SidecarState>();
+            let old_pid = state.pid.lock().unwrap().take();
+            #[cfg(windows)]
+            let old_job = state.job.lock().unwrap().take();
+            if let Some(old_pid) = old_pid {
+                // Ce kill est volontaire : on le signale avant de tuer le
+                // process pour que le thread de wait() ne le remonte pas
+                // comme un crash (ce qui redéclencherait aussitôt ce même
+                // chemin sur le sidecar qu'on vient de relancer). Si le
+                // process est déjà terminé, son thread de wait() a déjà
+                // tourné et le flag resterait levé pour le suivant.
+                if !terminated {
+                    state.expected_exit.store(true, Ordering::SeqCst);
+                }
+                super::shutdown::kill_sidecar(
+                    old_pid,
+                    #[cfg(windows)]
+                    old_job.as_ref(),
+                );
+            }
+
+            match spawn_sidecar(&app, port) {
+                Ok(()) => awaiting_recovery = true,
+                Err(e) => log_sidecar(&format!("Échec du redémarrage du sidecar : {}", e)),
+            }
+        }
+    });
+}